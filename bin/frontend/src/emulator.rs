@@ -0,0 +1,499 @@
+//! A small VT100/ANSI terminal emulator: a grid of styled cells fed by raw
+//! bytes from the PTY, rendered fresh from the grid on every draw instead of
+//! replaying stored strings.
+
+use ratzilla::ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+/// Parser state for the escape-sequence state machine. Kept as emulator state
+/// (rather than a local variable in `feed`) because an escape sequence can be
+/// split across separate WebSocket messages.
+enum ParserState {
+    Ground,
+    Escape,
+    Csi(String),
+    /// `ESC ( x` / `ESC ) x` charset designation: consume exactly one more
+    /// byte (the charset selector) then return to Ground.
+    EscapeCharset,
+    /// Inside an OSC/DCS/PM/APC escape we don't otherwise support, swallowing
+    /// bytes up to its terminator (BEL or ST, `ESC \`) instead of spilling the
+    /// sequence's body onto the grid as literal glyphs.
+    EscapeString,
+    /// Saw an `ESC` while swallowing an `EscapeString`; one more byte decides
+    /// whether this is the `\` of a string terminator or just part of the body.
+    EscapeStringEsc,
+}
+
+/// How many lines scrolled off the top of the grid are kept so the user can
+/// still scroll back to them.
+const MAX_SCROLLBACK: usize = 2000;
+
+pub struct Emulator {
+    grid: Vec<Vec<Cell>>,
+    // Lines pushed off the top of the grid by `line_feed`, oldest first.
+    scrollback: std::collections::VecDeque<Vec<Cell>>,
+    cols: usize,
+    rows: usize,
+    cursor_row: usize,
+    cursor_col: usize,
+    style: Style,
+    state: ParserState,
+    // Bytes carried over between `feed` calls when a multibyte UTF-8 sequence
+    // is split across a chunk boundary.
+    pending_utf8: Vec<u8>,
+}
+
+impl Emulator {
+    pub fn new(cols: usize, rows: usize) -> Self {
+        Self {
+            grid: vec![vec![Cell::default(); cols.max(1)]; rows.max(1)],
+            scrollback: std::collections::VecDeque::new(),
+            cols: cols.max(1),
+            rows: rows.max(1),
+            cursor_row: 0,
+            cursor_col: 0,
+            style: Style::default(),
+            state: ParserState::Ground,
+            pending_utf8: Vec::new(),
+        }
+    }
+
+    /// Number of lines available above the live grid to scroll back through.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Resize the live grid to match the terminal area, preserving whatever
+    /// content still fits. Lines already in `scrollback` keep their old width.
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        if cols == self.cols && rows == self.rows {
+            return;
+        }
+        let mut grid = vec![vec![Cell::default(); cols]; rows];
+        for (r, row) in self.grid.iter().enumerate().take(rows) {
+            for (c, cell) in row.iter().enumerate().take(cols) {
+                grid[r][c] = *cell;
+            }
+        }
+        self.grid = grid;
+        self.cols = cols;
+        self.rows = rows;
+        self.cursor_row = self.cursor_row.min(self.rows - 1);
+        self.cursor_col = self.cursor_col.min(self.cols - 1);
+    }
+
+    /// Clear the grid, scrollback and parser state back to a blank screen,
+    /// keeping the current size. Used when switching to a different session
+    /// mid-connection, since that session's replayed output has nothing to do
+    /// with whatever was already on screen.
+    pub fn reset(&mut self) {
+        self.grid = vec![vec![Cell::default(); self.cols]; self.rows];
+        self.scrollback.clear();
+        self.cursor_row = 0;
+        self.cursor_col = 0;
+        self.style = Style::default();
+        self.state = ParserState::Ground;
+        self.pending_utf8.clear();
+    }
+
+    /// Feed a chunk of raw stderr bytes through the parser, forcing a distinct
+    /// color so stderr output can be told apart from stdout in the grid.
+    pub fn feed_stderr(&mut self, bytes: &[u8]) {
+        let saved_style = self.style;
+        self.style = Style::default().fg(Color::LightRed);
+        self.feed(bytes);
+        self.style = saved_style;
+    }
+
+    /// Feed a chunk of raw PTY output through the parser.
+    ///
+    /// `pending_utf8` only needs to survive a multibyte sequence split across
+    /// a chunk boundary (the tail after `valid_up_to()`, when the error is an
+    /// unexpected end-of-input). A genuinely invalid byte *inside* the buffer
+    /// (real binary/non-UTF8 output, which `valid_up_to()` can also return 0
+    /// for) isn't something more bytes will ever fix — drop it as U+FFFD,
+    /// same as `error_len` tells us to, and keep decoding the rest of the
+    /// buffer instead of leaving it wedged in front of every future `feed`.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending_utf8.extend_from_slice(bytes);
+
+        loop {
+            match std::str::from_utf8(&self.pending_utf8) {
+                Ok(s) => {
+                    for ch in s.chars() {
+                        self.feed_char(ch);
+                    }
+                    self.pending_utf8.clear();
+                    return;
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    for ch in std::str::from_utf8(&self.pending_utf8[..valid]).unwrap().chars() {
+                        self.feed_char(ch);
+                    }
+                    match e.error_len() {
+                        Some(bad_len) => {
+                            // A real invalid byte sequence, not just a truncated one.
+                            self.feed_char('\u{fffd}');
+                            self.pending_utf8.drain(..valid + bad_len);
+                        }
+                        None => {
+                            // Truncated at the very end of the buffer; keep the
+                            // incomplete tail for the next `feed` to complete.
+                            self.pending_utf8.drain(..valid);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn feed_char(&mut self, ch: char) {
+        match std::mem::replace(&mut self.state, ParserState::Ground) {
+            ParserState::Ground => match ch {
+                '\x1b' => self.state = ParserState::Escape,
+                '\r' => self.cursor_col = 0,
+                '\n' => self.line_feed(),
+                '\x08' => self.cursor_col = self.cursor_col.saturating_sub(1),
+                _ => self.put_char(ch),
+            },
+            ParserState::Escape => match ch {
+                '[' => self.state = ParserState::Csi(String::new()),
+                // Charset designation (e.g. `ESC(B`): exactly one more byte
+                // to swallow, then back to Ground.
+                '(' | ')' => self.state = ParserState::EscapeCharset,
+                // OSC/DCS/PM/APC: a string of bytes terminated by BEL or ST
+                // (`ESC \`), e.g. a shell setting the window title with
+                // `ESC]0;...BEL`. Swallow the whole thing rather than
+                // spilling it onto the grid as literal glyphs.
+                ']' | 'P' | '^' | '_' => self.state = ParserState::EscapeString,
+                // Other single-byte escapes (cursor save/restore, etc.); nothing more to consume.
+                _ => {}
+            },
+            ParserState::EscapeCharset => {}
+            ParserState::EscapeString => match ch {
+                '\x07' => {} // BEL terminates the string
+                '\x1b' => self.state = ParserState::EscapeStringEsc,
+                _ => self.state = ParserState::EscapeString,
+            },
+            ParserState::EscapeStringEsc => match ch {
+                '\\' => {} // ST (ESC \) terminates the string
+                _ => self.state = ParserState::EscapeString,
+            },
+            ParserState::Csi(mut buf) => {
+                if ch.is_ascii_digit() || ch == ';' || ch == '?' {
+                    buf.push(ch);
+                    self.state = ParserState::Csi(buf);
+                } else {
+                    self.dispatch_csi(&buf, ch);
+                }
+            }
+        }
+    }
+
+    fn put_char(&mut self, ch: char) {
+        if self.cursor_col >= self.cols {
+            self.cursor_col = 0;
+            self.line_feed();
+        }
+        self.grid[self.cursor_row][self.cursor_col] = Cell {
+            ch,
+            style: self.style,
+        };
+        self.cursor_col += 1;
+    }
+
+    /// Advance to the next line, scrolling the grid up when the cursor would
+    /// otherwise move past the last row. The row scrolled off is kept in
+    /// `scrollback` rather than discarded.
+    fn line_feed(&mut self) {
+        if self.cursor_row + 1 >= self.rows {
+            let old_top = self.grid.remove(0);
+            if self.scrollback.len() >= MAX_SCROLLBACK {
+                self.scrollback.pop_front();
+            }
+            self.scrollback.push_back(old_top);
+            self.grid.push(vec![Cell::default(); self.cols]);
+        } else {
+            self.cursor_row += 1;
+        }
+    }
+
+    fn clear_line_from_cursor(&mut self) {
+        for cell in &mut self.grid[self.cursor_row][self.cursor_col..] {
+            *cell = Cell::default();
+        }
+    }
+
+    fn clear_from_cursor(&mut self) {
+        self.clear_line_from_cursor();
+        for row in &mut self.grid[self.cursor_row + 1..] {
+            row.fill(Cell::default());
+        }
+    }
+
+    fn clear_screen(&mut self) {
+        for row in &mut self.grid {
+            row.fill(Cell::default());
+        }
+    }
+
+    fn dispatch_csi(&mut self, params: &str, final_byte: char) {
+        let nums: Vec<i64> = if params.is_empty() {
+            Vec::new()
+        } else {
+            params
+                .split(';')
+                .map(|p| p.parse::<i64>().unwrap_or(0))
+                .collect()
+        };
+        let get = |i: usize| nums.get(i).copied().unwrap_or(0);
+        let move_by = |i: usize| {
+            let v = get(i);
+            if v == 0 { 1 } else { v }
+        };
+
+        match final_byte {
+            'H' | 'f' => {
+                let row = (move_by(0).max(1) - 1) as usize;
+                let col = (move_by(1).max(1) - 1) as usize;
+                self.cursor_row = row.min(self.rows - 1);
+                self.cursor_col = col.min(self.cols - 1);
+            }
+            'A' => self.cursor_row = self.cursor_row.saturating_sub(move_by(0) as usize),
+            'B' => {
+                self.cursor_row = (self.cursor_row + move_by(0) as usize).min(self.rows - 1)
+            }
+            'C' => {
+                self.cursor_col = (self.cursor_col + move_by(0) as usize).min(self.cols - 1)
+            }
+            'D' => self.cursor_col = self.cursor_col.saturating_sub(move_by(0) as usize),
+            'J' => match get(0) {
+                2 => self.clear_screen(),
+                _ => self.clear_from_cursor(),
+            },
+            'K' => {
+                if get(0) == 0 {
+                    self.clear_line_from_cursor();
+                }
+            }
+            'm' => self.apply_sgr(&nums),
+            // Unsupported CSI sequence; ignore rather than corrupt the grid.
+            _ => {}
+        }
+    }
+
+    fn apply_sgr(&mut self, nums: &[i64]) {
+        let nums: Vec<i64> = if nums.is_empty() { vec![0] } else { nums.to_vec() };
+        let mut i = 0;
+        while i < nums.len() {
+            match nums[i] {
+                0 => self.style = Style::default(),
+                1 => self.style = self.style.add_modifier(Modifier::BOLD),
+                7 => self.style = self.style.add_modifier(Modifier::REVERSED),
+                22 => self.style = self.style.remove_modifier(Modifier::BOLD),
+                27 => self.style = self.style.remove_modifier(Modifier::REVERSED),
+                30..=37 => self.style = self.style.fg(ansi_color((nums[i] - 30) as u8)),
+                39 => self.style = self.style.fg(Color::Reset),
+                40..=47 => self.style = self.style.bg(ansi_color((nums[i] - 40) as u8)),
+                49 => self.style = self.style.bg(Color::Reset),
+                90..=97 => self.style = self.style.fg(bright_ansi_color((nums[i] - 90) as u8)),
+                100..=107 => {
+                    self.style = self.style.bg(bright_ansi_color((nums[i] - 100) as u8))
+                }
+                38 | 48 => {
+                    let is_fg = nums[i] == 38;
+                    match nums.get(i + 1) {
+                        Some(5) => {
+                            if let Some(&n) = nums.get(i + 2) {
+                                let color = Color::Indexed(n as u8);
+                                self.style = if is_fg {
+                                    self.style.fg(color)
+                                } else {
+                                    self.style.bg(color)
+                                };
+                            }
+                            i += 2;
+                        }
+                        Some(2) => {
+                            if let (Some(&r), Some(&g), Some(&b)) =
+                                (nums.get(i + 2), nums.get(i + 3), nums.get(i + 4))
+                            {
+                                let color = Color::Rgb(r as u8, g as u8, b as u8);
+                                self.style = if is_fg {
+                                    self.style.fg(color)
+                                } else {
+                                    self.style.bg(color)
+                                };
+                            }
+                            i += 4;
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Build the lines to render this frame directly from the grid, merging
+    /// runs of cells that share a style into a single span.
+    pub fn render_lines(&self) -> Vec<Line<'static>> {
+        self.grid.iter().map(|row| render_row(row)).collect()
+    }
+
+    /// Build the lines to render when scrolled `offset` lines back from the
+    /// live bottom of the screen (`offset` 0 is identical to `render_lines`).
+    pub fn render_lines_at(&self, offset: usize) -> Vec<Line<'static>> {
+        let offset = offset.min(self.scrollback.len());
+        if offset == 0 {
+            return self.render_lines();
+        }
+
+        // The window is `self.rows` lines ending `offset` lines before the
+        // live bottom, drawn from whichever of scrollback/grid it falls in.
+        let total = self.scrollback.len() + self.rows;
+        let end = total - offset;
+        let start = end.saturating_sub(self.rows);
+
+        (start..end)
+            .map(|i| {
+                if i < self.scrollback.len() {
+                    render_row(&self.scrollback[i])
+                } else {
+                    render_row(&self.grid[i - self.scrollback.len()])
+                }
+            })
+            .collect()
+    }
+}
+
+/// Merge a row's runs of cells that share a style into a single styled span.
+fn render_row(row: &[Cell]) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut buffer = String::new();
+    let mut current_style = Style::default();
+    for (i, cell) in row.iter().enumerate() {
+        if i == 0 {
+            current_style = cell.style;
+        } else if cell.style != current_style {
+            spans.push(Span::styled(std::mem::take(&mut buffer), current_style));
+            current_style = cell.style;
+        }
+        buffer.push(cell.ch);
+    }
+    if !buffer.is_empty() {
+        spans.push(Span::styled(buffer, current_style));
+    }
+    Line::from(spans)
+}
+
+fn ansi_color(n: u8) -> Color {
+    match n {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::Gray,
+    }
+}
+
+fn bright_ansi_color(n: u8) -> Color {
+    match n {
+        0 => Color::DarkGray,
+        1 => Color::LightRed,
+        2 => Color::LightGreen,
+        3 => Color::LightYellow,
+        4 => Color::LightBlue,
+        5 => Color::LightMagenta,
+        6 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plain text of a row, trailing padding spaces included (callers trim
+    /// them), for assertions that don't care about styling.
+    fn row_text(emu: &Emulator, row: usize) -> String {
+        emu.grid[row].iter().map(|c| c.ch).collect::<String>()
+    }
+
+    #[test]
+    fn osc_title_sequence_is_swallowed_not_printed() {
+        let mut emu = Emulator::new(40, 5);
+        emu.feed(b"\x1b]0;host:~$\x07hello");
+        assert_eq!(row_text(&emu, 0).trim_end(), "hello");
+    }
+
+    #[test]
+    fn osc_sequence_terminated_by_st_is_swallowed() {
+        let mut emu = Emulator::new(40, 5);
+        emu.feed(b"\x1b]2;title\x1b\\hello");
+        assert_eq!(row_text(&emu, 0).trim_end(), "hello");
+    }
+
+    #[test]
+    fn charset_designation_is_swallowed_not_printed() {
+        let mut emu = Emulator::new(40, 5);
+        emu.feed(b"\x1b(Bhello");
+        assert_eq!(row_text(&emu, 0).trim_end(), "hello");
+    }
+
+    #[test]
+    fn csi_cursor_position_still_works_after_adding_escape_states() {
+        let mut emu = Emulator::new(40, 5);
+        emu.feed(b"\x1b[2;3Hx");
+        assert_eq!(emu.cursor_row, 1);
+        assert_eq!(emu.cursor_col, 3); // cursor advances past the 'x' it just printed
+        assert_eq!(row_text(&emu, 1).chars().nth(2), Some('x'));
+    }
+
+    #[test]
+    fn invalid_utf8_byte_is_skipped_instead_of_wedging_pending_buffer() {
+        let mut emu = Emulator::new(40, 5);
+        // A lone continuation byte (0x80) is never valid on its own.
+        emu.feed(&[0x80, b'h', b'i']);
+        assert_eq!(emu.pending_utf8.len(), 0);
+        assert_eq!(row_text(&emu, 0).trim_end(), "\u{fffd}hi");
+
+        // And decoding keeps working on the next feed instead of re-failing
+        // at the same (now-drained) offset forever.
+        emu.feed(b" there");
+        assert_eq!(row_text(&emu, 0).trim_end(), "\u{fffd}hi there");
+    }
+}
@@ -0,0 +1,186 @@
+//! An editable input line with cursor movement and a submitted-line history
+//! ring, so the input box behaves like a real prompt instead of only
+//! supporting append/backspace at the end of the line.
+
+use std::collections::VecDeque;
+
+/// Cap on how many submitted lines are kept for Up/Down recall.
+const MAX_HISTORY: usize = 256;
+
+pub struct InputLine {
+    buf: Vec<char>,
+    cursor: usize,
+    history: VecDeque<String>,
+    // Position while browsing `history` with Up/Down, and the in-progress
+    // line that was being edited before browsing started (restored once the
+    // user presses Down past the newest history entry).
+    browsing: Option<(usize, String)>,
+}
+
+impl InputLine {
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            cursor: 0,
+            history: VecDeque::new(),
+            browsing: None,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.buf.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.buf.insert(self.cursor, c);
+        self.cursor += 1;
+        self.browsing = None;
+    }
+
+    /// Delete the character before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.buf.remove(self.cursor);
+            self.browsing = None;
+        }
+    }
+
+    /// Delete the character under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor < self.buf.len() {
+            self.buf.remove(self.cursor);
+            self.browsing = None;
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.buf.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.buf.len();
+    }
+
+    /// Recall the previous (older) history entry, saving the in-progress line
+    /// the first time history browsing starts.
+    pub fn history_prev(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        let next_index = match &self.browsing {
+            Some((i, _)) => i.saturating_sub(1),
+            None => {
+                self.browsing = Some((self.history.len(), self.text()));
+                self.history.len() - 1
+            }
+        };
+        self.set_from_history(next_index);
+    }
+
+    /// Recall the next (newer) history entry, restoring the in-progress line
+    /// once the user moves past the newest entry.
+    pub fn history_next(&mut self) {
+        let Some((index, saved)) = self.browsing.clone() else {
+            return;
+        };
+        if index + 1 >= self.history.len() {
+            self.browsing = None;
+            self.buf = saved.chars().collect();
+            self.cursor = self.buf.len();
+        } else {
+            self.set_from_history(index + 1);
+        }
+    }
+
+    fn set_from_history(&mut self, index: usize) {
+        self.browsing = Some((index, self.browsing.as_ref().unwrap().1.clone()));
+        self.buf = self.history[index].chars().collect();
+        self.cursor = self.buf.len();
+    }
+
+    /// Submit the current line: push it onto the history ring and clear the
+    /// buffer, returning the submitted text.
+    pub fn submit(&mut self) -> String {
+        let text = self.text();
+        if !text.is_empty() {
+            if self.history.len() >= MAX_HISTORY {
+                self.history.pop_front();
+            }
+            self.history.push_back(text.clone());
+        }
+        self.buf.clear();
+        self.cursor = 0;
+        self.browsing = None;
+        text
+    }
+
+    /// Discard whatever's half-typed without submitting it, e.g. when Ctrl-C
+    /// interrupts the running program instead of the line it was composing.
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.cursor = 0;
+        self.browsing = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_backspace_move_the_cursor() {
+        let mut line = InputLine::new();
+        line.insert('a');
+        line.insert('b');
+        line.move_left();
+        line.insert('x');
+        assert_eq!(line.text(), "axb");
+        line.backspace();
+        assert_eq!(line.text(), "ab");
+        assert_eq!(line.cursor(), 1);
+    }
+
+    #[test]
+    fn history_prev_and_next_roundtrip_through_in_progress_line() {
+        let mut line = InputLine::new();
+        line.insert('a');
+        assert_eq!(line.submit(), "a");
+        line.insert('b');
+        assert_eq!(line.submit(), "b");
+
+        line.insert('c'); // in-progress, not yet submitted
+        line.history_prev();
+        assert_eq!(line.text(), "b");
+        line.history_prev();
+        assert_eq!(line.text(), "a");
+        line.history_next();
+        assert_eq!(line.text(), "b");
+        line.history_next(); // past the newest entry: restores the in-progress line
+        assert_eq!(line.text(), "c");
+    }
+
+    #[test]
+    fn clear_discards_without_recording_history() {
+        let mut line = InputLine::new();
+        line.insert('x');
+        line.clear();
+        assert_eq!(line.text(), "");
+        line.insert('a');
+        assert_eq!(line.submit(), "a");
+        line.history_prev();
+        assert_eq!(line.text(), "a"); // "x" was never recorded
+    }
+}
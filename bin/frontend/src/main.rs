@@ -1,60 +1,182 @@
+mod emulator;
+mod input;
+mod protocol;
+mod session;
+mod transport;
+
 use std::cell::RefCell;
-use std::collections::VecDeque;
 use std::io;
 use std::rc::Rc;
 
+use emulator::Emulator;
+use input::InputLine;
+use protocol::Frame;
 use ratzilla::ratatui::{
     Terminal,
     layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph},
 };
 use ratzilla::{DomBackend, WebRenderer, event::KeyCode};
-use wasm_bindgen::prelude::*;
-use web_sys::{MessageEvent, WebSocket};
-
-// Maximum number of messages to keep before removing oldest
-const MAX_MESSAGES: usize = 256;
+use transport::ClientTransport;
 
 const TITLE: &str = " App ";
 
 fn main() -> io::Result<()> {
     // Shared state
-    let messages = Rc::new(RefCell::new(VecDeque::with_capacity(MAX_MESSAGES)));
-    let input_buffer = Rc::new(RefCell::new(String::new()));
+    let emulator = Rc::new(RefCell::new(Emulator::new(80, 24)));
+    // `InputLine` composes a whole line locally (with its own cursor/history
+    // editing) and only sends it on Enter, plus an immediate Ctrl-C for
+    // SIGINT. That covers line-oriented programs (shells, REPLs) but not a
+    // raw keystroke-by-keystroke passthrough: arrows/Backspace never reach the
+    // PTY directly, so a full-screen program's own input handling (vim,
+    // htop, less, readline's own Up-arrow history) can't be driven through
+    // this terminal yet. Fixing that needs a raw/cooked input mode, not
+    // implemented here.
+    let input_line = Rc::new(RefCell::new(InputLine::new()));
+    // Lines scrolled back from the live bottom of the screen. The offset counts
+    // from the bottom, so output that arrives while scrolled back still moves
+    // the view along; the next submitted input brings it back to 0.
+    let scroll_offset = Rc::new(RefCell::new(0usize));
+    let exit_status = Rc::new(RefCell::new(None::<i32>));
     let backend = DomBackend::new()?;
     let terminal = Terminal::new(backend)?;
 
-    // Setup WebSocket
-    let ws = Rc::new(RefCell::new(setup_websocket(messages.clone())));
+    // Connect to the server, over WebSocket or WebTransport depending on how the page loaded
+    let conn = {
+        let emulator = emulator.clone();
+        let exit_status = exit_status.clone();
+        transport::connect(move |bytes| match protocol::decode(bytes) {
+            Some(Frame::Output(data)) => emulator.borrow_mut().feed(&data),
+            Some(Frame::Stderr(data)) => emulator.borrow_mut().feed_stderr(&data),
+            Some(Frame::Exit(code)) => *exit_status.borrow_mut() = Some(code),
+            // Input frames are the server's broadcast of what clients typed and
+            // are not drawn; Resize/Signal are client-to-server only.
+            Some(Frame::Input(_) | Frame::Resize { .. } | Frame::Signal(_)) => {}
+            // Not a binary frame at all — the only other thing the server sends
+            // unprompted is a `/list` reply (a bare JSON array, not frame-tagged).
+            None => {
+                if let Some(sessions) = session::decode_list_reply(bytes) {
+                    let line = if sessions.is_empty() {
+                        "(no sessions running)\r\n".to_string()
+                    } else {
+                        format!("Sessions: {}\r\n", sessions.join(", "))
+                    };
+                    emulator.borrow_mut().feed(line.as_bytes());
+                }
+            }
+        })
+    };
 
     // Handle keyboard input
     terminal.on_key_event({
-        let messages = messages.clone();
-        let input_buffer = input_buffer.clone();
-        let ws = ws.clone();
+        let emulator = emulator.clone();
+        let input_line = input_line.clone();
+        let scroll_offset = scroll_offset.clone();
+        let conn = conn.clone();
 
         move |key_event| {
             match key_event.code {
                 KeyCode::Enter => {
-                    // Send message when Enter is pressed
-                    let msg = input_buffer.borrow().clone();
-                    if !msg.is_empty() {
-                        if let Err(e) = ws.borrow().send_with_str(&msg) {
-                            add_message(&messages, format!("Send error: {:?}", e));
+                    // Send the submitted line and resume auto-follow, since the
+                    // user acting on the prompt implies they want to see the result.
+                    let msg = input_line.borrow_mut().submit();
+                    *scroll_offset.borrow_mut() = 0;
+                    if msg.is_empty() {
+                        // fall through, nothing to send
+                    } else if let Some(outcome) = session_command(&msg) {
+                        let bytes = match outcome {
+                            Ok(bytes) => bytes,
+                            Err(reason) => {
+                                emulator.borrow_mut().feed(format!("{reason}\r\n").as_bytes());
+                                return;
+                            }
+                        };
+                        // `/spawn`/`/attach` switch this connection to a different
+                        // session in place; the old session's output has nothing
+                        // to do with the new one, so start from a blank screen
+                        // rather than mixing the two on the same grid.
+                        let is_switch = msg.starts_with("/spawn ") || msg.starts_with("/attach ");
+                        if is_switch {
+                            emulator.borrow_mut().reset();
+                        }
+                        if let Err(e) = conn.send(&bytes) {
+                            emulator
+                                .borrow_mut()
+                                .feed(format!("Send error: {:?}\r\n", e).as_bytes());
+                        } else if is_switch {
+                            // A freshly spawned/attached session starts its PTY at
+                            // the server's hardcoded default size. The draw loop
+                            // only resends `Resize` when it detects a size
+                            // *change*, and switching sessions doesn't change the
+                            // browser's own terminal area, so it would otherwise
+                            // never correct this. Resend it explicitly, after the
+                            // switch message. A WebSocket delivers the two in
+                            // order, so the resize lands once the new session is
+                            // bridged; WebTransport datagrams are unordered, so
+                            // there the resize can reach the old session instead.
+                            let (cols, rows) = {
+                                let emu = emulator.borrow();
+                                (emu.cols() as u16, emu.rows() as u16)
+                            };
+                            let resize = protocol::encode(&Frame::Resize { cols, rows });
+                            let _ = conn.send(&resize);
+                        }
+                    } else {
+                        let frame = protocol::encode(&Frame::Input(msg));
+                        if let Err(e) = conn.send(&frame) {
+                            emulator
+                                .borrow_mut()
+                                .feed(format!("Send error: {:?}\r\n", e).as_bytes());
                         }
-                        input_buffer.borrow_mut().clear();
                     }
                 }
-                KeyCode::Backspace => {
-                    // Handle backspace
-                    input_buffer.borrow_mut().pop();
+                KeyCode::Backspace => input_line.borrow_mut().backspace(),
+                KeyCode::Delete => input_line.borrow_mut().delete(),
+                KeyCode::Home => input_line.borrow_mut().move_home(),
+                KeyCode::End => input_line.borrow_mut().move_end(),
+                KeyCode::Left => input_line.borrow_mut().move_left(),
+                KeyCode::Right => input_line.borrow_mut().move_right(),
+                // Shift+Up/Down scrolls back through program output; plain
+                // Up/Down instead recalls submitted input, like a shell history.
+                KeyCode::Up if key_event.shift => {
+                    let max = emulator.borrow().scrollback_len();
+                    let mut offset = scroll_offset.borrow_mut();
+                    *offset = (*offset + 1).min(max);
+                }
+                KeyCode::Down if key_event.shift => {
+                    let mut offset = scroll_offset.borrow_mut();
+                    *offset = offset.saturating_sub(1);
+                }
+                KeyCode::Up => input_line.borrow_mut().history_prev(),
+                KeyCode::Down => input_line.borrow_mut().history_next(),
+                KeyCode::PageUp => {
+                    let page = emulator.borrow().rows();
+                    let max = emulator.borrow().scrollback_len();
+                    let mut offset = scroll_offset.borrow_mut();
+                    *offset = (*offset + page).min(max);
                 }
-                KeyCode::Char(c) => {
-                    // Add character to input buffer
-                    input_buffer.borrow_mut().push(c);
+                KeyCode::PageDown => {
+                    let page = emulator.borrow().rows();
+                    let mut offset = scroll_offset.borrow_mut();
+                    *offset = offset.saturating_sub(page);
+                }
+                // Ctrl-C doesn't fit the line-buffered model the rest of this
+                // input uses: deliver it immediately as SIGINT instead of
+                // inserting a literal 'c', discarding whatever was half-typed
+                // rather than sending it first, so a runaway program can
+                // actually be interrupted.
+                KeyCode::Char('c') if key_event.ctrl => {
+                    input_line.borrow_mut().clear();
+                    let frame = protocol::encode(&Frame::Signal(2)); // SIGINT
+                    if let Err(e) = conn.send(&frame) {
+                        emulator
+                            .borrow_mut()
+                            .feed(format!("Send error: {:?}\r\n", e).as_bytes());
+                    }
                 }
+                KeyCode::Char(c) => input_line.borrow_mut().insert(c),
                 _ => {}
             }
         }
@@ -62,9 +184,14 @@ fn main() -> io::Result<()> {
 
     // Render loop
     terminal.draw_web(move |f| {
-        // Create outer border
+        // Create outer border, swapping in the exit status once the program has
+        // terminated since there's otherwise no sign the session has ended.
+        let title = match *exit_status.borrow() {
+            Some(code) => format!(" App — exited ({code}) "),
+            None => TITLE.to_string(),
+        };
         let outer_block = Block::default()
-            .title(TITLE)
+            .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::LightCyan));
 
@@ -95,33 +222,63 @@ fn main() -> io::Result<()> {
             )
             .split(inner_area);
 
-        // Render messages in the upper area
-        let msgs = messages.borrow();
-        let rows = chunks[0].height as usize;
-
-        // Create styled text from ANSI codes
-        let mut text = Text::default();
-        for msg in msgs.iter().skip(msgs.len().saturating_sub(rows)) {
-            let line = parse_ansi_to_line(msg);
-            text.lines.push(line);
+        // Keep the emulator's grid matched to the area it's drawn into, and let the
+        // backend know so the PTY's winsize stays correct.
+        let screen_area = chunks[0];
+        let cols = screen_area.width as usize;
+        let rows = screen_area.height as usize;
+        if cols > 0 && rows > 0 {
+            let mut emu = emulator.borrow_mut();
+            if (emu.cols(), emu.rows()) != (cols, rows) {
+                emu.resize(cols, rows);
+                let frame = protocol::encode(&Frame::Resize {
+                    cols: cols as u16,
+                    rows: rows as u16,
+                });
+                let _ = conn.send(&frame);
+            }
         }
 
+        // Render the terminal grid, scrolled back by `scroll_offset` lines when
+        // the user has paused auto-follow.
+        let offset = *scroll_offset.borrow();
+        let text = Text::from(emulator.borrow().render_lines_at(offset));
         f.render_widget(
             Paragraph::new(text).block(Block::default().borders(Borders::NONE)),
-            chunks[0],
+            screen_area,
         );
 
-        // Render input in the lower area
-        let input = input_buffer.borrow();
+        // Render input in the lower area, with the cursor drawn as a reversed
+        // cell at its actual position rather than always trailing the text.
+        let input = input_line.borrow();
+        let text = input.text();
+        let cursor = input.cursor().min(text.chars().count());
+        let (before, at_and_after) = split_at_char(&text, cursor);
+        let (at, after) = split_at_char(at_and_after, 1);
+        let cursor_span = if at.is_empty() {
+            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED))
+        } else {
+            Span::styled(at.to_string(), Style::default().add_modifier(Modifier::REVERSED))
+        };
+        let input_title = if offset > 0 {
+            format!(" Input (scrolled back {offset}) ")
+        } else {
+            " Input ".to_string()
+        };
         f.render_widget(
-            Paragraph::new(format!("> {}", input))
-                .block(
-                    Block::default()
-                        .title(" Input ")
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::LightMagenta)),
-                )
-                .alignment(Alignment::Left),
+            Paragraph::new(Line::from(vec![
+                Span::raw("> "),
+                Span::raw(before.to_string()),
+                cursor_span,
+                Span::raw(after.to_string()),
+            ]))
+            .block(
+                Block::default()
+                    .title(input_title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::LightMagenta)),
+            )
+            .alignment(Alignment::Left),
             chunks[1],
         );
     });
@@ -129,80 +286,36 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
-// Parse ANSI color codes to Ratatui spans
-fn parse_ansi_to_line(input: &str) -> Line<'static> {
-    let mut spans = Vec::new();
-    let mut current_style = Style::default();
-    let mut buffer = String::new();
-
-    let mut chars = input.chars().peekable();
-    while let Some(c) = chars.next() {
-        if c == '\x1b' {
-            // Handle ANSI escape sequence
-            if chars.next() == Some('[') {
-                let mut code = String::new();
-                while let Some(&c) = chars.peek() {
-                    if c == 'm' {
-                        chars.next(); // consume 'm'
-                        break;
-                    }
-                    code.push(chars.next().unwrap());
-                }
-
-                // Push the buffered text with current style
-                if !buffer.is_empty() {
-                    spans.push(Span::styled(buffer.clone(), current_style));
-                    buffer.clear();
-                }
-
-                // Update style based on ANSI code
-                current_style = match code.as_str() {
-                    "31" => Style::default().fg(Color::Red),
-                    "32" => Style::default().fg(Color::Green),
-                    "33" => Style::default().fg(Color::Yellow),
-                    "34" => Style::default().fg(Color::Blue),
-                    "35" => Style::default().fg(Color::Magenta),
-                    "36" => Style::default().fg(Color::Cyan),
-                    "37" => Style::default().fg(Color::White),
-                    "39" => Style::default(), // reset
-                    "90" => Style::default().fg(Color::Gray),
-                    _ => current_style,
-                };
-            }
-        } else {
-            buffer.push(c);
-        }
+/// Parse a submitted line as a `/list`, `/spawn <name>` or `/attach <name>`
+/// session-control command, encoding it to the bytes `conn.send` should
+/// carry. `None` means the line isn't a recognized command (including a bare
+/// `/` used by the program itself) and should be sent as ordinary terminal
+/// input instead. `Some(Err(reason))` means it *was* a recognized command but
+/// the session name was rejected (e.g. it contains a comma).
+fn session_command(msg: &str) -> Option<Result<Vec<u8>, &'static str>> {
+    if msg == "/list" {
+        return Some(Ok(session::encode_list()));
     }
-
-    // Push any remaining text
-    if !buffer.is_empty() {
-        spans.push(Span::styled(buffer, current_style));
+    if let Some(name) = msg.strip_prefix("/spawn ") {
+        return Some(
+            session::encode_spawn(name.trim())
+                .ok_or("Invalid session name (must be non-empty and contain no commas)"),
+        );
     }
-
-    Line::from(spans)
-}
-
-// Helper function to add messages with automatic pruning
-fn add_message(messages: &Rc<RefCell<VecDeque<String>>>, message: String) {
-    let mut msgs = messages.borrow_mut();
-    msgs.push_back(message);
-
-    // Remove oldest messages if we exceed the maximum
-    if msgs.len() > MAX_MESSAGES {
-        msgs.remove(0);
+    if let Some(name) = msg.strip_prefix("/attach ") {
+        return Some(
+            session::encode_attach(name.trim())
+                .ok_or("Invalid session name (must be non-empty and contain no commas)"),
+        );
     }
+    None
 }
 
-fn setup_websocket(messages: Rc<RefCell<VecDeque<String>>>) -> WebSocket {
-    let ws = WebSocket::new("/ws").unwrap();
-
-    let onmessage_callback = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
-        if let Some(text) = e.data().as_string() {
-            add_message(&messages, text);
-        }
-    });
-    ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
-    onmessage_callback.forget();
-
-    ws
+/// Split `s` at the `n`th char boundary, like `str::split_at` but indexed by
+/// chars instead of bytes so it can't land inside a multibyte sequence.
+fn split_at_char(s: &str, n: usize) -> (&str, &str) {
+    match s.char_indices().nth(n) {
+        Some((byte_idx, _)) => s.split_at(byte_idx),
+        None => (s, ""),
+    }
 }
@@ -0,0 +1,140 @@
+//! The typed binary protocol spoken with the server, mirroring
+//! `bin/server/src/protocol.rs`. Each message the transport hands back is
+//! already one complete frame (WebSocket/WebTransport preserve message
+//! boundaries), so unlike the server side there's no partial-frame
+//! reassembly to do here — just tag-based encode/decode of a single buffer.
+
+const TAG_INPUT: u8 = 0;
+const TAG_OUTPUT: u8 = 1;
+const TAG_STDERR: u8 = 2;
+const TAG_RESIZE: u8 = 3;
+const TAG_SIGNAL: u8 = 4;
+const TAG_EXIT: u8 = 5;
+
+/// One message in the terminal protocol. See the server's `protocol::Frame`
+/// for the wire format this mirrors.
+pub enum Frame {
+    Input(String),
+    Output(Vec<u8>),
+    Stderr(Vec<u8>),
+    Resize { cols: u16, rows: u16 },
+    Signal(u8),
+    Exit(i32),
+}
+
+/// Encode a single frame to bytes, ready to hand to `ClientTransport::send`.
+pub fn encode(frame: &Frame) -> Vec<u8> {
+    match frame {
+        Frame::Input(s) => encode_payload(TAG_INPUT, s.as_bytes()),
+        Frame::Output(b) => encode_payload(TAG_OUTPUT, b),
+        Frame::Stderr(b) => encode_payload(TAG_STDERR, b),
+        Frame::Resize { cols, rows } => {
+            let mut out = vec![TAG_RESIZE];
+            out.extend_from_slice(&cols.to_be_bytes());
+            out.extend_from_slice(&rows.to_be_bytes());
+            out
+        }
+        Frame::Signal(sig) => vec![TAG_SIGNAL, *sig],
+        Frame::Exit(code) => {
+            let mut out = vec![TAG_EXIT];
+            out.extend_from_slice(&code.to_be_bytes());
+            out
+        }
+    }
+}
+
+fn encode_payload(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(5 + payload.len());
+    out.push(tag);
+    out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Decode a single frame from one complete transport message.
+pub fn decode(data: &[u8]) -> Option<Frame> {
+    let (&tag, rest) = data.split_first()?;
+    match tag {
+        TAG_INPUT => {
+            let (len, payload) = take_payload(rest)?;
+            Some(Frame::Input(
+                String::from_utf8_lossy(&payload[..len]).into_owned(),
+            ))
+        }
+        TAG_OUTPUT => {
+            let (len, payload) = take_payload(rest)?;
+            Some(Frame::Output(payload[..len].to_vec()))
+        }
+        TAG_STDERR => {
+            let (len, payload) = take_payload(rest)?;
+            Some(Frame::Stderr(payload[..len].to_vec()))
+        }
+        TAG_RESIZE if rest.len() >= 4 => Some(Frame::Resize {
+            cols: u16::from_be_bytes(rest[0..2].try_into().ok()?),
+            rows: u16::from_be_bytes(rest[2..4].try_into().ok()?),
+        }),
+        TAG_SIGNAL => rest.first().map(|&sig| Frame::Signal(sig)),
+        TAG_EXIT if rest.len() >= 4 => Some(Frame::Exit(i32::from_be_bytes(
+            rest[0..4].try_into().ok()?,
+        ))),
+        _ => None,
+    }
+}
+
+fn take_payload(rest: &[u8]) -> Option<(usize, &[u8])> {
+    if rest.len() < 4 {
+        return None;
+    }
+    let len = u32::from_be_bytes(rest[0..4].try_into().ok()?) as usize;
+    let payload = rest.get(4..)?;
+    if payload.len() < len {
+        return None;
+    }
+    Some((len, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roundtrips(frame: Frame) {
+        let encoded = encode(&frame);
+        let decoded = decode(&encoded).expect("should decode what we just encoded");
+        match (frame, decoded) {
+            (Frame::Input(a), Frame::Input(b)) => assert_eq!(a, b),
+            (Frame::Output(a), Frame::Output(b)) => assert_eq!(a, b),
+            (Frame::Stderr(a), Frame::Stderr(b)) => assert_eq!(a, b),
+            (
+                Frame::Resize { cols: ac, rows: ar },
+                Frame::Resize { cols: bc, rows: br },
+            ) => assert_eq!((ac, ar), (bc, br)),
+            (Frame::Signal(a), Frame::Signal(b)) => assert_eq!(a, b),
+            (Frame::Exit(a), Frame::Exit(b)) => assert_eq!(a, b),
+            _ => panic!("decoded to the wrong variant"),
+        }
+    }
+
+    #[test]
+    fn every_variant_roundtrips() {
+        assert_roundtrips(Frame::Input("ls -la".to_string()));
+        assert_roundtrips(Frame::Output(b"hello\r\n".to_vec()));
+        assert_roundtrips(Frame::Stderr(b"oops".to_vec()));
+        assert_roundtrips(Frame::Resize { cols: 80, rows: 24 });
+        assert_roundtrips(Frame::Signal(2));
+        assert_roundtrips(Frame::Exit(-1));
+    }
+
+    #[test]
+    fn decode_rejects_an_unknown_tag() {
+        assert!(decode(b"\xffnonsense").is_none());
+    }
+
+    #[test]
+    fn decode_rejects_a_truncated_payload() {
+        // TAG_INPUT claims a 10-byte payload but only 2 bytes follow.
+        let mut bytes = vec![TAG_INPUT];
+        bytes.extend_from_slice(&10u32.to_be_bytes());
+        bytes.extend_from_slice(b"hi");
+        assert!(decode(&bytes).is_none());
+    }
+}
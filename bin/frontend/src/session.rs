@@ -0,0 +1,126 @@
+//! Client side of the session-control protocol, mirroring
+//! `bin/server/src/main.rs`'s `SessionControl`. These aren't `protocol::Frame`s —
+//! the server tells the two apart by trying to parse each message as this JSON
+//! shape before falling back to binary frame decoding — so they're encoded as
+//! plain JSON text, hand-rolled the same way `protocol.rs` hand-rolls the
+//! binary frames rather than pulling in `serde` for three small messages.
+
+/// List every currently-running session.
+pub fn encode_list() -> Vec<u8> {
+    b"{\"action\":\"list\"}".to_vec()
+}
+
+/// Create (or attach to, if it already exists) a new named session on this
+/// same connection, without reconnecting. `None` if `session` isn't a valid
+/// name (see `encode_session_action`).
+pub fn encode_spawn(session: &str) -> Option<Vec<u8>> {
+    encode_session_action("spawn", session)
+}
+
+/// Switch this connection over to an already-running session. `None` if
+/// `session` isn't a valid name (see `encode_session_action`).
+pub fn encode_attach(session: &str) -> Option<Vec<u8>> {
+    encode_session_action("attach", session)
+}
+
+/// A comma would be ambiguous with the delimiter `/list`'s reply uses between
+/// session names, so reject it here rather than only coping with it on the
+/// decode side.
+fn encode_session_action(action: &str, session: &str) -> Option<Vec<u8>> {
+    if session.is_empty() || session.contains(',') {
+        return None;
+    }
+    Some(
+        format!(
+            "{{\"action\":\"{action}\",\"session\":\"{}\"}}",
+            escape_json(session)
+        )
+        .into_bytes(),
+    )
+}
+
+fn escape_json(s: &str) -> String {
+    s.chars()
+        .flat_map(|c| match c {
+            '"' => vec!['\\', '"'],
+            '\\' => vec!['\\', '\\'],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Decode a `SessionControl::List` reply, a bare JSON array of session names
+/// (`registry.list()` serialized straight through, not wrapped in a `Frame`).
+/// Returns `None` for anything else so the caller can fall back to treating
+/// the bytes as a binary frame.
+///
+/// This walks the string char-by-char tracking quote state rather than
+/// `split(',')`-ing the array body, so a session name containing a comma
+/// doesn't get cut into two (malformed) entries.
+pub fn decode_list_reply(data: &[u8]) -> Option<Vec<String>> {
+    let text = std::str::from_utf8(data).ok()?.trim();
+    let inner = text.strip_prefix('[')?.strip_suffix(']')?;
+
+    let mut names = Vec::new();
+    let mut chars = inner.chars().peekable();
+    loop {
+        match chars.peek() {
+            None => break,
+            Some(c) if c.is_whitespace() || *c == ',' => {
+                chars.next();
+            }
+            Some('"') => {
+                chars.next();
+                let mut name = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            '"' => name.push('"'),
+                            '\\' => name.push('\\'),
+                            other => name.push(other),
+                        },
+                        c => name.push(c),
+                    }
+                }
+                names.push(name);
+            }
+            Some(_) => return None, // malformed entry
+        }
+    }
+    Some(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_session_names_with_a_comma() {
+        assert_eq!(encode_spawn("foo,bar"), None);
+        assert_eq!(encode_attach("foo,bar"), None);
+    }
+
+    #[test]
+    fn list_reply_with_comma_in_name_does_not_split_it() {
+        let reply = br#"["a,b","c"]"#;
+        assert_eq!(
+            decode_list_reply(reply),
+            Some(vec!["a,b".to_string(), "c".to_string()])
+        );
+    }
+
+    #[test]
+    fn list_reply_roundtrips_escaped_quotes() {
+        let reply = br#"["say \"hi\""]"#;
+        assert_eq!(
+            decode_list_reply(reply),
+            Some(vec!["say \"hi\"".to_string()])
+        );
+    }
+
+    #[test]
+    fn empty_list_reply_decodes_to_empty_vec() {
+        assert_eq!(decode_list_reply(b"[]"), Some(Vec::new()));
+    }
+}
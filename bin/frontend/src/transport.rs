@@ -0,0 +1,94 @@
+//! Abstraction over the client's connection to the server, so the rendering
+//! and input code doesn't care whether bytes travel over a WebSocket or
+//! (behind the `webtransport` feature) WebTransport.
+
+mod websocket;
+pub use websocket::WebSocketClientTransport;
+
+#[cfg(feature = "webtransport")]
+mod webtransport;
+#[cfg(feature = "webtransport")]
+pub use webtransport::WebTransportClientTransport;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+
+pub trait ClientTransport {
+    fn send(&self, data: &[u8]) -> Result<(), JsValue>;
+}
+
+/// Connect to the server, picking the protocol based on how the page itself was
+/// loaded: a `wt:` origin selects WebTransport (when the feature is enabled),
+/// anything else falls back to a plain WebSocket.
+pub fn connect(on_message: impl Fn(&[u8]) + 'static) -> Rc<dyn ClientTransport> {
+    #[cfg(feature = "webtransport")]
+    {
+        if uses_webtransport() {
+            return connect_webtransport_with_fallback(on_message);
+        }
+    }
+    Rc::new(WebSocketClientTransport::connect(on_message))
+}
+
+#[cfg(feature = "webtransport")]
+fn uses_webtransport() -> bool {
+    web_sys::window()
+        .and_then(|w| w.location().protocol().ok())
+        .map(|scheme| scheme == "wt:")
+        .unwrap_or(false)
+}
+
+/// Try WebTransport, falling back to a WebSocket if it never manages to
+/// establish (construction fails outright, or the handshake itself fails)
+/// instead of leaving the caller stuck with a connection that can never
+/// carry any data.
+#[cfg(feature = "webtransport")]
+fn connect_webtransport_with_fallback(on_message: impl Fn(&[u8]) + 'static) -> Rc<dyn ClientTransport> {
+    let on_message: Rc<dyn Fn(&[u8])> = Rc::new(on_message);
+    let active: Rc<RefCell<Option<Rc<dyn ClientTransport>>>> = Rc::new(RefCell::new(None));
+
+    let fall_back = {
+        let active = active.clone();
+        let on_message = on_message.clone();
+        move || {
+            let ws = WebSocketClientTransport::connect({
+                let on_message = on_message.clone();
+                move |bytes| on_message(bytes)
+            });
+            *active.borrow_mut() = Some(Rc::new(ws) as Rc<dyn ClientTransport>);
+        }
+    };
+
+    let wt = WebTransportClientTransport::connect(
+        {
+            let on_message = on_message.clone();
+            move |bytes| on_message(bytes)
+        },
+        fall_back.clone(),
+    );
+    match wt {
+        Ok(wt) => *active.borrow_mut() = Some(Rc::new(wt) as Rc<dyn ClientTransport>),
+        Err(_) => fall_back(),
+    }
+
+    Rc::new(SwitchableTransport(active))
+}
+
+/// Routes sends to whichever transport is currently active, so a WebTransport
+/// connection can later be swapped out for a WebSocket without the caller
+/// needing a new handle.
+#[cfg(feature = "webtransport")]
+struct SwitchableTransport(Rc<RefCell<Option<Rc<dyn ClientTransport>>>>);
+
+#[cfg(feature = "webtransport")]
+impl ClientTransport for SwitchableTransport {
+    fn send(&self, data: &[u8]) -> Result<(), JsValue> {
+        match self.0.borrow().as_ref() {
+            Some(active) => active.send(data),
+            // Still connecting; drop the send rather than buffering it — the
+            // caller (keystrokes, resize) will just act again shortly after.
+            None => Ok(()),
+        }
+    }
+}
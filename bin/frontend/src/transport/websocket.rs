@@ -0,0 +1,36 @@
+use super::ClientTransport;
+use js_sys::Uint8Array;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen::prelude::*;
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+pub struct WebSocketClientTransport {
+    ws: WebSocket,
+}
+
+impl WebSocketClientTransport {
+    pub fn connect(on_message: impl Fn(&[u8]) + 'static) -> Self {
+        // Every client lands in the "default" session unless it later sends an
+        // `attach`/`spawn` control message to switch to a named one.
+        let ws = WebSocket::new("/ws/default").unwrap();
+        ws.set_binary_type(BinaryType::Arraybuffer);
+
+        let onmessage_callback = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+            if let Ok(buf) = e.data().dyn_into::<js_sys::ArrayBuffer>() {
+                on_message(&Uint8Array::new(&buf).to_vec());
+            } else if let Some(text) = e.data().as_string() {
+                on_message(text.as_bytes());
+            }
+        });
+        ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
+        onmessage_callback.forget();
+
+        Self { ws }
+    }
+}
+
+impl ClientTransport for WebSocketClientTransport {
+    fn send(&self, data: &[u8]) -> Result<(), JsValue> {
+        self.ws.send_with_u8_array(data)
+    }
+}
@@ -0,0 +1,76 @@
+//! WebTransport (HTTP/3 / QUIC) client transport, used instead of a WebSocket
+//! when the page is loaded over the `wt:` scheme. Datagrams avoid the
+//! head-of-line blocking a single WebSocket connection imposes on bursty
+//! terminal output.
+
+use super::ClientTransport;
+use js_sys::Uint8Array;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::WebTransport;
+
+pub struct WebTransportClientTransport {
+    transport: WebTransport,
+}
+
+impl WebTransportClientTransport {
+    /// Attempt to open a WebTransport session, pumping received datagrams into
+    /// `on_message`. Fails immediately if the browser doesn't support the API;
+    /// if the handshake itself later fails to establish, `on_lost` is called
+    /// once so the caller can fall back to a WebSocket instead of talking to a
+    /// connection that will never carry any data.
+    pub fn connect(
+        on_message: impl Fn(&[u8]) + 'static,
+        on_lost: impl FnOnce() + 'static,
+    ) -> Result<Self, JsValue> {
+        let transport = WebTransport::new("/wt")?;
+
+        // Datagram reads come off the async reader returned by `datagrams().readable()`;
+        // pump it into `on_message` on a local task since `connect` itself is sync.
+        let reader = transport.datagrams().readable();
+        wasm_bindgen_futures::spawn_local(async move {
+            let reader: web_sys::ReadableStreamDefaultReader = reader
+                .get_reader()
+                .unchecked_into();
+            loop {
+                let Ok(result) = JsFuture::from(reader.read()).await else {
+                    break;
+                };
+                let chunk = js_sys::Reflect::get(&result, &"value".into()).unwrap();
+                if chunk.is_undefined() {
+                    break;
+                }
+                let bytes = Uint8Array::new(&chunk).to_vec();
+                on_message(&bytes);
+            }
+        });
+
+        // If the handshake never completes (e.g. the server has no
+        // WebTransport endpoint), `ready` rejects; surface that as a one-shot
+        // fallback rather than leaving the caller stuck.
+        wasm_bindgen_futures::spawn_local({
+            let transport = transport.clone();
+            async move {
+                if JsFuture::from(transport.ready()).await.is_err() {
+                    on_lost();
+                }
+            }
+        });
+
+        Ok(Self { transport })
+    }
+}
+
+impl ClientTransport for WebTransportClientTransport {
+    fn send(&self, data: &[u8]) -> Result<(), JsValue> {
+        // The writable side is an async WritableStream; fire the write without
+        // waiting for it since `ClientTransport::send` is a synchronous call.
+        let writer = self.transport.datagrams().writable().get_writer()?;
+        let chunk = Uint8Array::from(data);
+        wasm_bindgen_futures::spawn_local(async move {
+            let _ = JsFuture::from(writer.write_with_chunk(&chunk)).await;
+        });
+        Ok(())
+    }
+}
@@ -1,25 +1,45 @@
+mod protocol;
+mod session;
+mod transport;
+
 use axum::{
     Router,
-    extract::{
-        ConnectInfo, State,
-        ws::{Message, WebSocket, WebSocketUpgrade},
-    },
+    extract::{ConnectInfo, Path as AxumPath, State, ws::WebSocketUpgrade},
     response::IntoResponse,
     routing::get,
 };
-use futures_util::{SinkExt as _, StreamExt as _};
-use std::{collections::VecDeque, env, ffi::OsStr, net::SocketAddr, path::Path, sync::Arc};
-use tokio::{
-    io::{AsyncBufReadExt as _, AsyncWriteExt as _},
-    sync::{RwLock, broadcast, mpsc},
+use bytes::{Bytes, BytesMut};
+use futures_util::StreamExt as _;
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use protocol::{Frame, FrameCodec, encode_frame};
+use session::{AllowedCommand, SessionRegistry};
+use std::{
+    collections::{HashMap, VecDeque},
+    env,
+    ffi::OsStr,
+    io::{Read as _, Write as _},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::codec::Decoder as _;
 use tower_http::services::ServeDir;
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
+use transport::{Transport, TransportReceiver, WebSocketReceiver, WebSocketTransport};
 
 // Maximum number of messages to keep before removing oldest
 const MAX_MESSAGES: usize = 256;
 
+// Terminal size assumed until the client reports its real dimensions via a resize message.
+const DEFAULT_PTY_SIZE: PtySize = PtySize {
+    rows: 24,
+    cols: 80,
+    pixel_width: 0,
+    pixel_height: 0,
+};
+
 #[tokio::main]
 async fn main() {
     // Initialize logging with info level as default
@@ -31,27 +51,39 @@ async fn main() {
 
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
-        println!("Usage: cargo r -r -- <target_executable> [target_args...]");
+        println!("Usage: cargo r -r -- <name>=<executable> [<name>=<executable>...]");
         return;
     }
-    let target_exe = Path::new(&args[1]);
-    if !target_exe.exists() {
-        println!();
-    }
-    let working_dir = target_exe.parent().unwrap();
-    let target_args = &args[2..];
-    // Create our program handler
-    let program_handler = Arc::new(
-        ProgramHandler::new(target_exe, working_dir, target_args)
-            .await
-            .expect("Failed to start program"),
-    );
+
+    let mut allowed = HashMap::new();
+    for spec in &args[1..] {
+        match spec.split_once('=') {
+            Some((name, path)) => {
+                allowed.insert(
+                    name.to_string(),
+                    AllowedCommand {
+                        path: PathBuf::from(path),
+                    },
+                );
+            }
+            None => println!("Ignoring malformed allow-list entry (expected name=path): {spec}"),
+        }
+    }
+    if allowed.is_empty() {
+        println!("No valid <name>=<executable> entries given, nothing to serve.");
+        return;
+    }
+
+    let registry = Arc::new(SessionRegistry::new(allowed));
+
+    #[cfg(feature = "webtransport")]
+    tokio::spawn(serve_webtransport(Arc::clone(&registry)));
 
     // Build our application with shared state
     let app = Router::new()
         .fallback_service(ServeDir::new("html"))
-        .route("/ws", get(ws_handler))
-        .with_state(program_handler);
+        .route("/ws/:session", get(ws_handler))
+        .with_state(registry);
 
     // Run the server
     let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
@@ -70,185 +102,473 @@ async fn main() {
     .unwrap();
 }
 
-/// WebSocket handler that bridges clients to the program
+/// Accept WebTransport (HTTP/3 over QUIC) sessions and bridge each one into
+/// `handle_connection` the same way `ws_handler` does for WebSockets, so a
+/// client that picks WebTransport ends up in the same session machinery.
+#[cfg(feature = "webtransport")]
+async fn serve_webtransport(registry: Arc<SessionRegistry>) {
+    use transport::webtransport::{WebTransportReceiver, WebTransportTransport};
+    use wtransport::{Endpoint, Identity, ServerConfig};
+
+    // Self-signed: fine for local/dev use over `wt://`. A real deployment
+    // would load a certificate issued for its actual hostname instead.
+    let identity = Identity::self_signed(["localhost"])
+        .expect("failed to generate a self-signed WebTransport certificate");
+    let config = ServerConfig::builder()
+        .with_bind_default(4433)
+        .with_identity(identity)
+        .build();
+
+    let endpoint = match Endpoint::server(config) {
+        Ok(endpoint) => endpoint,
+        Err(e) => {
+            tracing::error!("Failed to bind WebTransport endpoint: {}", e);
+            return;
+        }
+    };
+    tracing::info!(
+        "WebTransport listening on https://{} (wt://)",
+        endpoint.local_addr().expect("bound endpoint has a local address")
+    );
+
+    loop {
+        let incoming = endpoint.accept().await;
+        let registry = Arc::clone(&registry);
+
+        tokio::spawn(async move {
+            let session_request = match incoming.await {
+                Ok(req) => req,
+                Err(e) => {
+                    tracing::error!("WebTransport session request failed: {}", e);
+                    return;
+                }
+            };
+            // Clients connect to `/wt/:session`, mirroring the WebSocket `/ws/:session`
+            // route — except the frontend always requests the bare `/wt` (no session
+            // segment), which `trim_start_matches("/wt/")` leaves untouched since there's
+            // no trailing slash to match. Strip the `/wt` prefix on its own first, so both
+            // `/wt` and `/wt/` fall through to the same "default" as an empty segment.
+            let session_id = session_request
+                .path()
+                .strip_prefix("/wt")
+                .unwrap_or(session_request.path())
+                .trim_start_matches('/')
+                .to_string();
+            let session_id = if session_id.is_empty() {
+                "default".to_string()
+            } else {
+                session_id
+            };
+
+            let connection = match session_request.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::error!("WebTransport handshake failed: {}", e);
+                    return;
+                }
+            };
+            let addr = connection.remote_address();
+
+            handle_connection(
+                WebTransportTransport::new(connection.clone()),
+                WebTransportReceiver::new(connection),
+                addr,
+                registry,
+                session_id,
+            )
+            .await;
+        });
+    }
+}
+
+/// Session-management control messages, mirroring a multi-room model where
+/// each room is an independent program with its own scrollback and input
+/// broadcast channel.
+#[derive(serde::Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum SessionControl {
+    /// List every currently-running session.
+    List,
+    /// Create (or attach to, if it already exists) a new named session on this
+    /// same connection, without reconnecting the WebSocket.
+    Spawn { session: String },
+    /// Switch this connection over to an already-running session.
+    Attach { session: String },
+}
+
+/// WebSocket handler that bridges clients to a named session, spawning it from
+/// the allow-listed command set on first connect.
 async fn ws_handler(
     ws: WebSocketUpgrade,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    State(program_handler): State<Arc<ProgramHandler>>,
+    AxumPath(session): AxumPath<String>,
+    State(registry): State<Arc<SessionRegistry>>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_connection(socket, addr, program_handler))
+    ws.on_upgrade(move |socket| {
+        let (sink, stream) = socket.split();
+        handle_connection(
+            WebSocketTransport::new(sink),
+            WebSocketReceiver::new(stream),
+            addr,
+            registry,
+            session,
+        )
+    })
 }
 
-/// Handle an individual WebSocket connection
-async fn handle_connection(
-    socket: WebSocket,
+/// Bridge a client connection to its session, switching to a different session
+/// in place whenever a `spawn`/`attach` control message asks for it.
+async fn handle_connection<T, R>(
+    mut sender: T,
+    mut receiver: R,
     addr: SocketAddr,
-    program_handler: Arc<ProgramHandler>,
-) {
-    let (mut ws_sender, mut ws_receiver) = socket.split();
+    registry: Arc<SessionRegistry>,
+    mut session_id: String,
+) where
+    T: Transport + 'static,
+    R: TransportReceiver + 'static,
+{
+    loop {
+        let program_handler = match registry.attach(&session_id, DEFAULT_PTY_SIZE).await {
+            Ok(handler) => handler,
+            Err(e) => {
+                tracing::error!("Failed to attach to session '{session_id}': {e}");
+                return;
+            }
+        };
 
-    // Subscribe to program output and input history
-    let (mut program_rx, initial_messages) = program_handler.subscribe().await;
-    let stdin_tx = program_handler.get_stdin_tx();
+        let outcome = bridge_session(&mut sender, &mut receiver, &program_handler, &registry).await;
+        registry.release(&session_id).await;
 
-    // Send initial messages (both input and output history)
-    for msg in initial_messages {
-        if ws_sender.send(Message::Text(msg.into())).await.is_err() {
-            return;
+        match outcome {
+            BridgeOutcome::SwitchTo(next) => session_id = next,
+            BridgeOutcome::Closed => break,
         }
     }
 
-    // Spawn task to forward program messages to WebSocket
-    let send_task = tokio::spawn(async move {
-        while let Ok(msg) = program_rx.recv().await {
-            if ws_sender.send(Message::Text(msg.into())).await.is_err() {
-                break;
-            }
+    tracing::info!("Connection closed: {}", addr);
+}
+
+enum BridgeOutcome {
+    /// A `spawn`/`attach` control message asked to move to a different session.
+    SwitchTo(String),
+    Closed,
+}
+
+/// Forward one session's output to the client and the client's input back to
+/// it, until the connection closes or a control message asks to switch session.
+async fn bridge_session<T, R>(
+    sender: &mut T,
+    receiver: &mut R,
+    program_handler: &Arc<ProgramHandler>,
+    registry: &Arc<SessionRegistry>,
+) -> BridgeOutcome
+where
+    T: Transport,
+    R: TransportReceiver,
+{
+    let (mut program_rx, initial_frames) = program_handler.subscribe().await;
+    let stdin_tx = program_handler.get_stdin_tx();
+    let mut codec = FrameCodec;
+    let mut decode_buf = BytesMut::new();
+
+    // Send initial frames (both input and output/stderr/exit history)
+    for frame in initial_frames {
+        if sender.send(encode_frame(frame)).await.is_err() {
+            return BridgeOutcome::Closed;
         }
-    });
-
-    // Spawn task to forward WebSocket messages to program stdin
-    let recv_task = tokio::spawn(async move {
-        while let Some(Ok(Message::Text(input))) = ws_receiver.next().await {
-            // Broadcast the input to all clients before sending to program
-            if let Err(e) = program_handler.broadcast_input(&input).await {
-                tracing::error!("Failed to broadcast input: {}", e);
-                break;
+    }
+
+    loop {
+        tokio::select! {
+            frame = program_rx.recv() => {
+                match frame {
+                    Ok(frame) => {
+                        if sender.send(encode_frame(frame)).await.is_err() {
+                            return BridgeOutcome::Closed;
+                        }
+                    }
+                    Err(_) => return BridgeOutcome::Closed,
+                }
             }
+            data = receiver.recv() => {
+                let Some(data) = data else {
+                    return BridgeOutcome::Closed;
+                };
 
-            // Send to program stdin
-            if stdin_tx.send(input.to_string()).is_err() {
-                break;
+                // Session routing (`spawn`/`attach`/`list`) is a separate, plain-JSON
+                // control channel predating the binary frame protocol; try it first
+                // since a valid `Frame` never happens to also be valid JSON text.
+                if let Ok(text) = std::str::from_utf8(&data) {
+                    if let Ok(control) = serde_json::from_str::<SessionControl>(text) {
+                        match control {
+                            SessionControl::List => {
+                                let sessions = registry.list().await;
+                                let reply = serde_json::to_vec(&sessions).unwrap_or_default();
+                                if sender.send(Bytes::from(reply)).await.is_err() {
+                                    return BridgeOutcome::Closed;
+                                }
+                            }
+                            SessionControl::Spawn { session } | SessionControl::Attach { session } => {
+                                return BridgeOutcome::SwitchTo(session);
+                            }
+                        }
+                        continue;
+                    }
+                }
+
+                decode_buf.extend_from_slice(&data);
+                loop {
+                    match codec.decode(&mut decode_buf) {
+                        Ok(Some(Frame::Input(text))) => {
+                            if let Err(e) = program_handler.broadcast_input(&text).await {
+                                tracing::error!("Failed to broadcast input: {}", e);
+                                return BridgeOutcome::Closed;
+                            }
+                            if stdin_tx.send(text).is_err() {
+                                return BridgeOutcome::Closed;
+                            }
+                        }
+                        Ok(Some(Frame::Resize { cols, rows })) => {
+                            if let Err(e) = program_handler.resize(cols, rows) {
+                                tracing::error!("Failed to resize pty: {}", e);
+                            }
+                        }
+                        Ok(Some(Frame::Signal(sig))) => {
+                            if let Err(e) = program_handler.signal(sig) {
+                                tracing::error!("Failed to deliver signal {}: {}", sig, e);
+                            }
+                        }
+                        // Output/Stderr/Exit are server-to-client only; ignore rather
+                        // than error if a client sends one.
+                        Ok(Some(Frame::Output(_) | Frame::Stderr(_) | Frame::Exit(_))) => {}
+                        Ok(None) => break,
+                        Err(e) => {
+                            // There's no way to resynchronize to the next frame boundary
+                            // inside a corrupted/unknown-tag byte stream, so rather than
+                            // leaving `decode_buf` wedged on these same leading bytes
+                            // forever, drop the connection and let the client reconnect.
+                            tracing::error!("Failed to decode frame: {}", e);
+                            return BridgeOutcome::Closed;
+                        }
+                    }
+                }
             }
         }
-    });
-
-    // Wait for either task to complete
-    tokio::select! {
-        _ = send_task => {},
-        _ = recv_task => {},
     }
-
-    tracing::info!("Connection closed: {}", addr);
 }
 
 /// ProgramHandler implementation with input broadcasting
-struct ProgramHandler {
-    program_handle: tokio::process::Child,
+pub(crate) struct ProgramHandler {
+    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    pty_master: Box<dyn MasterPty + Send>,
     stdin_tx: mpsc::UnboundedSender<String>,
-    message_tx: broadcast::Sender<String>,
-    message_buf: Arc<RwLock<VecDeque<String>>>,
+    message_tx: broadcast::Sender<Frame>,
+    message_buf: Arc<Mutex<VecDeque<Frame>>>,
 }
 
 impl ProgramHandler {
-    async fn new<S, P>(
+    pub(crate) async fn new<S, P>(
         program_path: S,
         working_dir_path: P,
         args: &[String],
+        initial_size: PtySize,
     ) -> std::io::Result<Self>
     where
         S: AsRef<OsStr>,
         P: AsRef<Path>,
     {
-        let mut program_handle = tokio::process::Command::new(program_path)
-            .current_dir(working_dir_path)
-            .args(args)
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .spawn()?;
+        let pty_system = native_pty_system();
+        let pty_pair = pty_system.openpty(initial_size).map_err(to_io_error)?;
+
+        let mut cmd = CommandBuilder::new(program_path.as_ref());
+        cmd.cwd(working_dir_path.as_ref());
+        cmd.args(args);
+        // Interactive/full-screen programs (curses, readline, raw mode) need a real TERM
+        // to pick terminfo entries rather than falling back to dumb line output.
+        cmd.env("TERM", "xterm-256color");
 
-        let message_buf = Arc::new(RwLock::new(VecDeque::with_capacity(MAX_MESSAGES)));
-        let (message_tx, _) = broadcast::channel(MAX_MESSAGES);
+        let child: Arc<Mutex<Box<dyn Child + Send + Sync>>> =
+            Arc::new(Mutex::new(pty_pair.slave.spawn_command(cmd).map_err(to_io_error)?));
+        // Drop our copy of the slave side: once the child's copy closes too, the
+        // master's reader will see EOF instead of blocking forever.
+        drop(pty_pair.slave);
+
+        let message_buf = Arc::new(Mutex::new(VecDeque::with_capacity(MAX_MESSAGES)));
+        let (message_tx, _) = broadcast::channel::<Frame>(MAX_MESSAGES);
 
         // Setup stdin writer
-        let mut program_stdin = program_handle.stdin.take().unwrap();
+        let mut pty_writer = pty_pair.master.take_writer().map_err(to_io_error)?;
         let (stdin_tx, mut stdin_rx) = mpsc::unbounded_channel::<String>();
 
-        tokio::spawn(async move {
-            while let Some(msg) = stdin_rx.recv().await {
-                if let Err(e) = async {
-                    program_stdin.write_all(msg.as_bytes()).await?;
-                    program_stdin.write_all(b"\n").await?;
-                    program_stdin.flush().await
-                }
-                .await
+        tokio::task::spawn_blocking(move || {
+            while let Some(msg) = stdin_rx.blocking_recv() {
+                if let Err(e) = pty_writer
+                    .write_all(msg.as_bytes())
+                    .and_then(|_| pty_writer.write_all(b"\n"))
+                    .and_then(|_| pty_writer.flush())
                 {
-                    tracing::error!("Failed to write to stdin: {}", e);
+                    tracing::error!("Failed to write to pty: {}", e);
                     break;
                 }
             }
         });
 
-        // Setup stdout reader
-        let program_stdout = program_handle.stdout.take().unwrap();
-        let mut program_out_reader = tokio::io::BufReader::new(program_stdout);
-        let message_tx_clone2 = message_tx.clone();
-        let message_buf_clone2 = Arc::clone(&message_buf);
+        // Setup pty output reader. portable-pty's reader is a blocking `Read`, so it
+        // runs on its own thread rather than tying up the tokio runtime.
+        //
+        // Note: stdout and stderr both land on the pty's single master fd (that's
+        // what gives the child a real controlling terminal, raw mode and all), so
+        // there's no way to tell them apart here. `Frame::Stderr` is reserved for
+        // a future non-interactive spawn mode that pipes stderr separately; every
+        // byte read off the pty today is forwarded as `Frame::Output`.
+        let mut pty_reader = pty_pair.master.try_clone_reader().map_err(to_io_error)?;
+        let message_tx_clone = message_tx.clone();
+        let message_buf_clone = Arc::clone(&message_buf);
+        let child_clone = Arc::clone(&child);
 
-        tokio::spawn(async move {
-            let mut buf = String::new();
+        std::thread::spawn(move || {
+            // Output is raw terminal bytes, not newline-delimited UTF-8, so we read fixed-size
+            // chunks and forward them unchanged. Reassembling any multibyte sequence split
+            // across a chunk boundary is left to whatever eventually decodes it for display.
+            let mut buf = [0u8; 4096];
             loop {
-                buf.clear();
-                match program_out_reader.read_line(&mut buf).await {
+                match pty_reader.read(&mut buf) {
                     Ok(0) => break, // EOF
-                    Ok(_) => {
-                        let trimmed = buf.trim().to_string();
-
-                        // Broadcast and store output
-                        let _ = message_tx_clone2.send(trimmed.clone());
-                        let mut message_buf = message_buf_clone2.write().await;
-                        if message_buf.len() >= MAX_MESSAGES {
-                            message_buf.pop_front();
-                        }
-                        message_buf.push_back(trimmed);
+                    Ok(n) => {
+                        push_frame(
+                            &message_tx_clone,
+                            &message_buf_clone,
+                            Frame::Output(Bytes::copy_from_slice(&buf[..n])),
+                        );
                     }
                     Err(e) => {
-                        tracing::error!("Read error: {}", e);
+                        tracing::error!("PTY read error: {}", e);
                         break;
                     }
                 }
             }
+
+            // EOF on the pty means the child's side has closed; wait for its real
+            // exit status and forward it so the client shows a result instead of
+            // the session just going quiet. `Child::wait()` blocks until the
+            // process actually exits, and by that point it almost always has
+            // (EOF on the pty usually means the child is gone already), but
+            // holding `child`'s lock for that blocking call would serialize
+            // `signal()`/`Drop::kill()` behind it for as long as it takes —
+            // exactly the calls meant to make the process exit sooner. Poll
+            // the non-blocking `try_wait()` instead, holding the lock only for
+            // the instant of each check.
+            let code = loop {
+                match child_clone.lock().unwrap().try_wait() {
+                    Ok(Some(status)) => break status.exit_code() as i32,
+                    Ok(None) => std::thread::sleep(std::time::Duration::from_millis(50)),
+                    Err(e) => {
+                        tracing::error!("Failed to wait on child: {}", e);
+                        break -1;
+                    }
+                }
+            };
+            push_frame(&message_tx_clone, &message_buf_clone, Frame::Exit(code));
         });
 
         Ok(Self {
-            program_handle,
+            child,
+            pty_master: pty_pair.master,
             stdin_tx,
             message_tx,
             message_buf,
         })
     }
 
-    /// Subscribe to both input and output messages
-    async fn subscribe(&self) -> (broadcast::Receiver<String>, Vec<String>) {
-        let buf = self.message_buf.read().await;
+    /// Subscribe to both input and output/stderr/exit frames
+    async fn subscribe(&self) -> (broadcast::Receiver<Frame>, Vec<Frame>) {
+        let buf = self.message_buf.lock().unwrap();
         (self.message_tx.subscribe(), buf.clone().into())
     }
 
     /// Broadcast input to all clients and store in history
     async fn broadcast_input(&self, input: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let input = input.to_string();
-        // Broadcast to all clients
-        self.message_tx.send(input.clone())?;
-
-        // Store in history
-        let mut message_buf = self.message_buf.write().await;
-        if message_buf.len() >= MAX_MESSAGES {
-            message_buf.pop_front();
-        }
-        message_buf.push_back(input);
-
+        push_frame(
+            &self.message_tx,
+            &self.message_buf,
+            Frame::Input(input.to_string()),
+        )?;
         Ok(())
     }
 
     fn get_stdin_tx(&self) -> mpsc::UnboundedSender<String> {
         self.stdin_tx.clone()
     }
+
+    /// Apply a new terminal size to the PTY so the child process's `winsize` matches
+    /// what the browser's terminal is actually showing.
+    fn resize(&self, cols: u16, rows: u16) -> std::io::Result<()> {
+        self.pty_master
+            .resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(to_io_error)
+    }
+
+    /// Deliver a POSIX signal to the running program.
+    fn signal(&self, sig: u8) -> std::io::Result<()> {
+        let pid = self
+            .child
+            .lock()
+            .unwrap()
+            .process_id()
+            .ok_or_else(|| std::io::Error::other("child has already exited"))?;
+        send_signal(pid, sig)
+    }
+}
+
+/// Broadcast a frame to current subscribers and store it in the replay buffer.
+fn push_frame(
+    tx: &broadcast::Sender<Frame>,
+    buf: &Arc<Mutex<VecDeque<Frame>>>,
+    frame: Frame,
+) -> Result<usize, broadcast::error::SendError<Frame>> {
+    let mut buf = buf.lock().unwrap();
+    if buf.len() >= MAX_MESSAGES {
+        buf.pop_front();
+    }
+    buf.push_back(frame.clone());
+    tx.send(frame)
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, sig: u8) -> std::io::Result<()> {
+    // A single syscall doesn't justify pulling in `libc`; declare just the symbol we need.
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+    let rc = unsafe { kill(pid as i32, sig as i32) };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn send_signal(_pid: u32, _sig: u8) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "signal delivery is not supported on this platform",
+    ))
 }
 
 impl Drop for ProgramHandler {
     fn drop(&mut self) {
-        if let Err(e) = self.program_handle.start_kill() {
+        if let Err(e) = self.child.lock().unwrap().kill() {
             tracing::error!("Failed to kill child process: {}", e);
         }
     }
 }
+
+fn to_io_error(e: impl std::error::Error + Send + Sync + 'static) -> std::io::Error {
+    std::io::Error::other(e)
+}
@@ -0,0 +1,196 @@
+//! The typed, length-framed wire protocol spoken between the server and
+//! clients, replacing the old scheme where input, output, and control data
+//! were all just strings indistinguishable from one another in a single
+//! broadcast channel.
+//!
+//! Each frame is a one-byte tag followed by a tag-specific payload; the three
+//! variable-length payloads (`Input`/`Output`/`Stderr`) are additionally
+//! length-prefixed so `FrameCodec` can reassemble a frame split across reads
+//! (or pick multiple frames out of one, if the transport ever batches them).
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// One message in the terminal protocol. `Input` flows client-to-server and is
+/// broadcast back to every subscriber; `Resize`/`Signal` flow client-to-server;
+/// `Output`/`Stderr`/`Exit` flow server-to-client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// A line of input a client sent, broadcast to every subscriber so
+    /// multiple attached terminals see each other's keystrokes.
+    Input(String),
+    /// Raw bytes the program wrote to stdout.
+    Output(Bytes),
+    /// Raw bytes the program wrote to stderr, kept separate so the frontend
+    /// can render it distinctly from stdout.
+    Stderr(Bytes),
+    /// A client-reported terminal size, applied to the PTY so the program's
+    /// `winsize` matches the browser's actual dimensions.
+    Resize { cols: u16, rows: u16 },
+    /// A POSIX signal number to deliver to the running program.
+    Signal(u8),
+    /// The program's real exit status, sent once the child has terminated.
+    Exit(i32),
+}
+
+const TAG_INPUT: u8 = 0;
+const TAG_OUTPUT: u8 = 1;
+const TAG_STDERR: u8 = 2;
+const TAG_RESIZE: u8 = 3;
+const TAG_SIGNAL: u8 = 4;
+const TAG_EXIT: u8 = 5;
+
+/// `Encoder`/`Decoder` pair for `Frame`, meant to be used the way
+/// `tokio_util::codec::Framed` uses any codec: fed whatever bytes the
+/// transport hands back, accumulating in a `BytesMut` until a whole frame is
+/// available.
+#[derive(Default)]
+pub struct FrameCodec;
+
+impl Encoder<Frame> for FrameCodec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> std::io::Result<()> {
+        match frame {
+            Frame::Input(s) => encode_payload(dst, TAG_INPUT, s.as_bytes()),
+            Frame::Output(b) => encode_payload(dst, TAG_OUTPUT, &b),
+            Frame::Stderr(b) => encode_payload(dst, TAG_STDERR, &b),
+            Frame::Resize { cols, rows } => {
+                dst.put_u8(TAG_RESIZE);
+                dst.put_u16(cols);
+                dst.put_u16(rows);
+            }
+            Frame::Signal(sig) => {
+                dst.put_u8(TAG_SIGNAL);
+                dst.put_u8(sig);
+            }
+            Frame::Exit(code) => {
+                dst.put_u8(TAG_EXIT);
+                dst.put_i32(code);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn encode_payload(dst: &mut BytesMut, tag: u8, payload: &[u8]) {
+    dst.put_u8(tag);
+    dst.put_u32(payload.len() as u32);
+    dst.extend_from_slice(payload);
+}
+
+impl Decoder for FrameCodec {
+    type Item = Frame;
+    type Error = std::io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> std::io::Result<Option<Frame>> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        let tag = src[0];
+        let needed = match tag {
+            TAG_INPUT | TAG_OUTPUT | TAG_STDERR => {
+                if src.len() < 5 {
+                    return Ok(None);
+                }
+                5 + u32::from_be_bytes(src[1..5].try_into().unwrap()) as usize
+            }
+            TAG_RESIZE | TAG_EXIT => 5,
+            TAG_SIGNAL => 2,
+            other => return Err(std::io::Error::other(format!("unknown frame tag: {other}"))),
+        };
+        if src.len() < needed {
+            return Ok(None);
+        }
+
+        let mut buf = src.split_to(needed);
+        buf.advance(1); // tag, already matched on above
+        let frame = match tag {
+            TAG_INPUT => {
+                let len = buf.get_u32() as usize;
+                Frame::Input(String::from_utf8_lossy(&buf[..len]).into_owned())
+            }
+            TAG_OUTPUT => {
+                let len = buf.get_u32() as usize;
+                Frame::Output(buf.split_to(len).freeze())
+            }
+            TAG_STDERR => {
+                let len = buf.get_u32() as usize;
+                Frame::Stderr(buf.split_to(len).freeze())
+            }
+            TAG_RESIZE => Frame::Resize {
+                cols: buf.get_u16(),
+                rows: buf.get_u16(),
+            },
+            TAG_SIGNAL => Frame::Signal(buf.get_u8()),
+            TAG_EXIT => Frame::Exit(buf.get_i32()),
+            _ => unreachable!("tag already validated above"),
+        };
+        Ok(Some(frame))
+    }
+}
+
+/// Encode a single frame to bytes, for the common case of sending one frame
+/// per outgoing transport message.
+pub fn encode_frame(frame: Frame) -> Bytes {
+    let mut buf = BytesMut::new();
+    FrameCodec
+        .encode(frame, &mut buf)
+        .expect("Frame encoding is infallible");
+    buf.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(frame: Frame) {
+        let mut buf = BytesMut::new();
+        FrameCodec.encode(frame.clone(), &mut buf).unwrap();
+        assert_eq!(FrameCodec.decode(&mut buf).unwrap(), Some(frame));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn every_variant_roundtrips() {
+        roundtrip(Frame::Input("ls -la".to_string()));
+        roundtrip(Frame::Output(Bytes::from_static(b"hello\r\n")));
+        roundtrip(Frame::Stderr(Bytes::from_static(b"oops")));
+        roundtrip(Frame::Resize { cols: 80, rows: 24 });
+        roundtrip(Frame::Signal(2));
+        roundtrip(Frame::Exit(-1));
+    }
+
+    #[test]
+    fn decode_reassembles_a_frame_split_across_reads() {
+        let full = encode_frame(Frame::Input("hi".to_string()));
+        let (first, second) = full.split_at(3);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(first);
+        assert_eq!(FrameCodec.decode(&mut buf).unwrap(), None);
+
+        buf.extend_from_slice(second);
+        assert_eq!(
+            FrameCodec.decode(&mut buf).unwrap(),
+            Some(Frame::Input("hi".to_string()))
+        );
+    }
+
+    #[test]
+    fn decode_picks_multiple_frames_out_of_one_buffer() {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&encode_frame(Frame::Signal(1)));
+        buf.extend_from_slice(&encode_frame(Frame::Signal(2)));
+
+        assert_eq!(FrameCodec.decode(&mut buf).unwrap(), Some(Frame::Signal(1)));
+        assert_eq!(FrameCodec.decode(&mut buf).unwrap(), Some(Frame::Signal(2)));
+        assert_eq!(FrameCodec.decode(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn decode_errors_on_an_unknown_tag() {
+        let mut buf = BytesMut::from(&b"\xffnonsense"[..]);
+        assert!(FrameCodec.decode(&mut buf).is_err());
+    }
+}
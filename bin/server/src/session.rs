@@ -0,0 +1,91 @@
+//! Session registry: one `ProgramHandler` per named session, spawned on demand
+//! from an allow-listed command set and torn down once its last subscriber
+//! disconnects.
+
+use crate::ProgramHandler;
+use portable_pty::PtySize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+
+/// A command the server is willing to spawn, keyed by the name clients pass in
+/// the `/ws/:session` path. Session ids may additionally carry a `#label` suffix
+/// (e.g. `bash#2`) to open a second, independent instance of the same command.
+pub struct AllowedCommand {
+    pub path: PathBuf,
+}
+
+struct SessionEntry {
+    handler: Arc<ProgramHandler>,
+    subscribers: usize,
+}
+
+pub struct SessionRegistry {
+    sessions: RwLock<HashMap<String, SessionEntry>>,
+    allowed: HashMap<String, AllowedCommand>,
+}
+
+impl SessionRegistry {
+    pub fn new(allowed: HashMap<String, AllowedCommand>) -> Self {
+        Self {
+            sessions: RwLock::new(HashMap::new()),
+            allowed,
+        }
+    }
+
+    fn command_name(session_id: &str) -> &str {
+        session_id.split('#').next().unwrap_or(session_id)
+    }
+
+    /// Return the session's `ProgramHandler`, spawning it from the allow-list if
+    /// this is the first subscriber, and bump its subscriber count.
+    pub async fn attach(
+        &self,
+        session_id: &str,
+        size: PtySize,
+    ) -> std::io::Result<Arc<ProgramHandler>> {
+        if let Some(entry) = self.sessions.write().await.get_mut(session_id) {
+            entry.subscribers += 1;
+            return Ok(Arc::clone(&entry.handler));
+        }
+
+        let command_name = Self::command_name(session_id);
+        let command = self.allowed.get(command_name).ok_or_else(|| {
+            std::io::Error::other(format!("unknown session command: {command_name}"))
+        })?;
+        let working_dir = command.path.parent().unwrap_or_else(|| Path::new("."));
+        let handler = Arc::new(ProgramHandler::new(&command.path, working_dir, &[], size).await?);
+
+        // Another connection may have spawned the same session while we were starting
+        // ours; keep whichever is already registered and let our redundant spawn's
+        // `ProgramHandler` (and its child) be dropped instead of leaking it.
+        let mut sessions = self.sessions.write().await;
+        let entry = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionEntry {
+                handler,
+                subscribers: 0,
+            });
+        entry.subscribers += 1;
+        Ok(Arc::clone(&entry.handler))
+    }
+
+    /// Drop a subscriber's interest in a session, tearing it down (killing the
+    /// child) once nobody is left attached.
+    pub async fn release(&self, session_id: &str) {
+        let mut sessions = self.sessions.write().await;
+        if let Some(entry) = sessions.get_mut(session_id) {
+            entry.subscribers = entry.subscribers.saturating_sub(1);
+            if entry.subscribers == 0 {
+                sessions.remove(session_id);
+            }
+        }
+    }
+
+    pub async fn list(&self) -> Vec<String> {
+        self.sessions.read().await.keys().cloned().collect()
+    }
+}
@@ -0,0 +1,81 @@
+//! A transport-agnostic bridge between a client connection and a `ProgramHandler`.
+//!
+//! `handle_connection` only knows about the `Transport`/`TransportReceiver` trait
+//! pair, so the program-bridging logic doesn't care whether bytes travel over a
+//! WebSocket or (behind the `webtransport` feature) WebTransport.
+
+#[cfg(feature = "webtransport")]
+pub mod webtransport;
+
+use async_trait::async_trait;
+use axum::extract::ws::{Message, WebSocket};
+use bytes::Bytes;
+use futures_util::{
+    SinkExt as _, StreamExt as _,
+    stream::{SplitSink, SplitStream},
+};
+
+/// The sending half of a client connection.
+#[async_trait]
+pub trait Transport: Send {
+    async fn send(&mut self, data: Bytes) -> std::io::Result<()>;
+    async fn close(&mut self);
+}
+
+/// The receiving half of a client connection.
+#[async_trait]
+pub trait TransportReceiver: Send {
+    async fn recv(&mut self) -> Option<Bytes>;
+}
+
+/// `Transport` over an axum WebSocket.
+pub struct WebSocketTransport {
+    sink: SplitSink<WebSocket, Message>,
+}
+
+impl WebSocketTransport {
+    pub fn new(sink: SplitSink<WebSocket, Message>) -> Self {
+        Self { sink }
+    }
+}
+
+#[async_trait]
+impl Transport for WebSocketTransport {
+    async fn send(&mut self, data: Bytes) -> std::io::Result<()> {
+        self.sink
+            .send(Message::Binary(data))
+            .await
+            .map_err(std::io::Error::other)
+    }
+
+    async fn close(&mut self) {
+        let _ = self.sink.close().await;
+    }
+}
+
+/// `TransportReceiver` over an axum WebSocket. Text frames are passed through as
+/// their UTF-8 bytes so callers can decode control messages the same way
+/// regardless of whether they arrived as a text or binary frame.
+pub struct WebSocketReceiver {
+    stream: SplitStream<WebSocket>,
+}
+
+impl WebSocketReceiver {
+    pub fn new(stream: SplitStream<WebSocket>) -> Self {
+        Self { stream }
+    }
+}
+
+#[async_trait]
+impl TransportReceiver for WebSocketReceiver {
+    async fn recv(&mut self) -> Option<Bytes> {
+        while let Some(Ok(msg)) = self.stream.next().await {
+            match msg {
+                Message::Text(text) => return Some(Bytes::from(text.to_string().into_bytes())),
+                Message::Binary(data) => return Some(data),
+                _ => continue,
+            }
+        }
+        None
+    }
+}
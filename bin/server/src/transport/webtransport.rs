@@ -0,0 +1,51 @@
+//! WebTransport (HTTP/3 over QUIC) implementation of the `Transport`/
+//! `TransportReceiver` pair, used when a client connects over the `wt://` scheme
+//! instead of upgrading to a WebSocket. Datagrams give lower latency and no
+//! head-of-line blocking for the continuous stream of terminal output, at the
+//! cost of being unordered and loss-tolerant rather than reliable.
+
+use super::{Transport, TransportReceiver};
+use async_trait::async_trait;
+use bytes::Bytes;
+use wtransport::Connection;
+
+pub struct WebTransportTransport {
+    connection: Connection,
+}
+
+impl WebTransportTransport {
+    pub fn new(connection: Connection) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait]
+impl Transport for WebTransportTransport {
+    async fn send(&mut self, data: Bytes) -> std::io::Result<()> {
+        self.connection
+            .send_datagram(data)
+            .map_err(std::io::Error::other)
+    }
+
+    async fn close(&mut self) {
+        self.connection.close(0u32.into(), b"closed");
+    }
+}
+
+pub struct WebTransportReceiver {
+    connection: Connection,
+}
+
+impl WebTransportReceiver {
+    pub fn new(connection: Connection) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait]
+impl TransportReceiver for WebTransportReceiver {
+    async fn recv(&mut self) -> Option<Bytes> {
+        let datagram = self.connection.receive_datagram().await.ok()?;
+        Some(Bytes::copy_from_slice(datagram.payload()))
+    }
+}